@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use sql::Sql;
+use sql::{QueryBuilder, Sql, ToSql};
 
 pub mod sql;
 
@@ -8,10 +8,13 @@ pub mod sql;
 const NOT_OPERATOR: char = '!';
 const OR_OPERATOR: char = '|';
 const AND_OPERATOR: char = '&';
-const DOMAIN_OPERATORS: [char; 3] = [NOT_OPERATOR, OR_OPERATOR, AND_OPERATOR];
+/// Every valid [DomainItem::Operator] character. [Domain::parse] rejects any
+/// other character with [DomainError::UnknownOperator].
+pub const DOMAIN_OPERATORS: [char; 3] = [NOT_OPERATOR, OR_OPERATOR, AND_OPERATOR];
 
-/// Term operators.
-const TERM_OPERATORS: [&str; 19] = [
+/// Every valid [DomainItem::Term] comparison operator. [Domain::parse] rejects
+/// any other string with [DomainError::UnknownTermOperator].
+pub const TERM_OPERATORS: [&str; 19] = [
     "=",
     "!=",
     "<=",
@@ -33,10 +36,12 @@ const TERM_OPERATORS: [&str; 19] = [
     "not any",
 ];
 
-const NEGATIVE_TERM_OPERATORS: [&str; 4] = ["!=", "not like", "not ilike", "not in"];
+/// The subset of [TERM_OPERATORS] whose SQL rendering is itself a negation
+/// (e.g. `NOT LIKE`), as opposed to being wrapped in [Domain::Not].
+pub const NEGATIVE_TERM_OPERATORS: [&str; 4] = ["!=", "not like", "not ilike", "not in"];
 
-pub fn sql_operators() -> HashMap<&'static str, Sql<'static>> {
-    let map  = HashMap::from([
+pub fn sql_operators() -> HashMap<&'static str, Sql> {
+    HashMap::from([
         ("=", Sql::new("=", None)),
         ("!=", Sql::new("!=", None)),
         ("<=", Sql::new("<=", None)),
@@ -51,9 +56,256 @@ pub fn sql_operators() -> HashMap<&'static str, Sql<'static>> {
         ("ilike", Sql::new("ILIKE", None)),
         ("not like", Sql::new("NOT LIKE", None)),
         ("not ilike", Sql::new("NOT ILIKE", None)),
-    ]);
+    ])
+}
+
+/// One element of a flat, Odoo-style prefix-notation domain.
+///
+/// A domain is a list of these: logical operators (`&`, `|`, `!`) followed,
+/// in prefix order, by the terms (or sub-expressions) they apply to.
+pub enum DomainItem<'a> {
+    /// A logical operator: `&` (AND), `|` (OR), or `!` (NOT).
+    Operator(char),
+    /// A `(field, operator, value)` comparison, e.g. `("age", ">=", &18)`.
+    Term(&'a str, &'a str, &'a dyn ToSql),
+}
+
+/// An error produced while parsing a [DomainItem] list into a [Domain].
+#[derive(Debug, PartialEq, Eq)]
+pub enum DomainError {
+    /// `&`, `|`, or `!` didn't have enough operands left on the stack.
+    MissingOperand(char),
+    /// A character was used as an operator but isn't one of `&`, `|`, `!`.
+    UnknownOperator(char),
+    /// A term used an operator that isn't one of [TERM_OPERATORS].
+    UnknownTermOperator(String),
+}
+
+/// A parsed domain expression, ready to be compiled into [Sql].
+///
+/// Build one with [Domain::parse], then turn it into SQL with [Domain::compile].
+pub enum Domain<'a> {
+    /// The empty domain: matches every row.
+    All,
+    /// A single `(field, operator, value)` comparison.
+    Term(&'a str, &'a str, &'a dyn ToSql),
+    /// Both children must match.
+    And(Box<Domain<'a>>, Box<Domain<'a>>),
+    /// Either child must match.
+    Or(Box<Domain<'a>>, Box<Domain<'a>>),
+    /// The child must not match.
+    Not(Box<Domain<'a>>),
+}
+
+impl<'a> Domain<'a> {
+    /// Parse a flat, Odoo-style prefix-notation domain into a [Domain] tree.
+    ///
+    /// The list is scanned right-to-left with a stack: terms are pushed as leaves,
+    /// `!` pops one node and negates it, and `&`/`|` pop two nodes and combine them.
+    /// Any operands left on the stack once the list is exhausted are implicitly
+    /// AND-ed together, matching Odoo's implicit-AND rule. An empty domain parses
+    /// to [Domain::All].
+    ///
+    /// Every operator is checked against [DOMAIN_OPERATORS] and every term's
+    /// comparison operator against [TERM_OPERATORS] as it's encountered, so a
+    /// malformed domain is rejected here rather than silently compiling to
+    /// garbage SQL in [Domain::compile].
+    pub fn parse(items: &[DomainItem<'a>]) -> Result<Domain<'a>, DomainError> {
+        let mut stack: Vec<Domain<'a>> = Vec::new();
+
+        for item in items.iter().rev() {
+            match item {
+                DomainItem::Term(field, op, value) => {
+                    if !TERM_OPERATORS.contains(op) {
+                        return Err(DomainError::UnknownTermOperator((*op).to_owned()));
+                    }
+                    stack.push(Domain::Term(field, op, *value));
+                }
+                DomainItem::Operator(op) if !DOMAIN_OPERATORS.contains(op) => {
+                    return Err(DomainError::UnknownOperator(*op));
+                }
+                DomainItem::Operator(op) if *op == NOT_OPERATOR => {
+                    let operand = stack.pop().ok_or(DomainError::MissingOperand(*op))?;
+                    stack.push(Domain::Not(Box::new(operand)));
+                }
+                DomainItem::Operator(op) => {
+                    let left = stack.pop().ok_or(DomainError::MissingOperand(*op))?;
+                    let right = stack.pop().ok_or(DomainError::MissingOperand(*op))?;
+                    stack.push(if *op == AND_OPERATOR {
+                        Domain::And(Box::new(left), Box::new(right))
+                    } else {
+                        Domain::Or(Box::new(left), Box::new(right))
+                    });
+                }
+            }
+        }
+
+        // Anything left on the stack is implicitly AND-ed together, left to right.
+        let mut result = stack.pop().unwrap_or(Domain::All);
+        while let Some(next) = stack.pop() {
+            result = Domain::And(Box::new(result), Box::new(next));
+        }
+
+        Ok(result)
+    }
+
+    /// Compile this domain into a [Sql] fragment using [sql_operators].
+    ///
+    /// A convenience wrapper around [Domain::compile_into] that targets a fresh
+    /// [Sql] object.
+    pub fn compile(&self) -> Sql {
+        let mut out = Sql::new("", None);
+        self.compile_into(&mut out);
 
-    map
+        out
+    }
+
+    /// Compile this domain into any [QueryBuilder], using [sql_operators].
+    ///
+    /// Each leaf pushes a `"field" <op> ?` fragment with the value bound as its
+    /// parameter; `And`/`Or` wrap their children in parentheses joined by
+    /// `AND`/`OR`; `Not` wraps its child in `NOT (...)`. [Domain::All] compiles
+    /// to a match-all `1=1`.
+    pub fn compile_into<B: QueryBuilder>(&self, out: &mut B) {
+        match self {
+            Domain::All => out.push_sql("1=1"),
+            Domain::Term(field, op, value) => {
+                // `op` was already checked against TERM_OPERATORS in `parse`, so
+                // a `None` here just means it's one of the handful of Odoo
+                // operators (e.g. `child_of`) with no entry in `sql_operators` —
+                // it's passed through verbatim rather than being rejected.
+                let operators = sql_operators();
+                let sql_op = match operators.get(*op) {
+                    Some(sql) => sql.query(),
+                    None => (*op).to_owned(),
+                };
+                out.push_identifier(field);
+                out.push_sql(&format!(" {sql_op} "));
+                out.push_param(value.to_sql());
+            }
+            Domain::Not(inner) => {
+                out.push_sql("NOT (");
+                inner.compile_into(out);
+                out.push_sql(")");
+            }
+            Domain::And(left, right) | Domain::Or(left, right) => {
+                let joiner = if matches!(self, Domain::And(..)) {
+                    "AND"
+                } else {
+                    "OR"
+                };
+                out.push_sql("(");
+                left.compile_into(out);
+                out.push_sql(&format!(") {joiner} ("));
+                right.compile_into(out);
+                out.push_sql(")");
+            }
+        }
+    }
 }
 
-pub enum Domain {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql::Value;
+
+    #[test]
+    fn empty_domain_matches_all() {
+        let domain = Domain::parse(&[]).unwrap();
+        assert_eq!("1=1", domain.compile().query());
+    }
+
+    #[test]
+    fn single_term() {
+        let age = 18;
+        let items = [DomainItem::Term("age", ">=", &age)];
+        let domain = Domain::parse(&items).unwrap();
+        assert_eq!(r#""age" >= ?"#, domain.compile().query());
+    }
+
+    #[test]
+    fn implicit_and_between_terms() {
+        let active = true;
+        let age = 18;
+        let items = [
+            DomainItem::Term("active", "=", &active),
+            DomainItem::Term("age", ">=", &age),
+        ];
+        let domain = Domain::parse(&items).unwrap();
+        assert_eq!(
+            r#"("active" = ?) AND ("age" >= ?)"#,
+            domain.compile().query()
+        );
+    }
+
+    #[test]
+    fn explicit_or() {
+        let a = 1;
+        let b = 2;
+        let items = [
+            DomainItem::Operator('|'),
+            DomainItem::Term("id", "=", &a),
+            DomainItem::Term("id", "=", &b),
+        ];
+        let domain = Domain::parse(&items).unwrap();
+        assert_eq!(r#"("id" = ?) OR ("id" = ?)"#, domain.compile().query());
+    }
+
+    #[test]
+    fn not_negates_the_following_subtree() {
+        let active = false;
+        let items = [
+            DomainItem::Operator('!'),
+            DomainItem::Term("active", "=", &active),
+        ];
+        let domain = Domain::parse(&items).unwrap();
+        assert_eq!(r#"NOT ("active" = ?)"#, domain.compile().query());
+    }
+
+    #[test]
+    fn missing_operand_is_an_error_not_a_panic() {
+        let items = [DomainItem::Operator('&')];
+        match Domain::parse(&items) {
+            Err(e) => assert_eq!(DomainError::MissingOperand('&'), e),
+            Ok(_) => panic!("expected a MissingOperand error"),
+        }
+    }
+
+    #[test]
+    fn unknown_operator_is_an_error() {
+        let active = true;
+        let items = [
+            DomainItem::Operator('~'),
+            DomainItem::Term("active", "=", &active),
+        ];
+        match Domain::parse(&items) {
+            Err(e) => assert_eq!(DomainError::UnknownOperator('~'), e),
+            Ok(_) => panic!("expected an UnknownOperator error"),
+        }
+    }
+
+    #[test]
+    fn unknown_term_operator_is_an_error_not_passed_through() {
+        let age = 18;
+        let items = [DomainItem::Term("age", "bogus", &age)];
+        match Domain::parse(&items) {
+            Err(e) => assert_eq!(DomainError::UnknownTermOperator("bogus".to_owned()), e),
+            Ok(_) => panic!("expected an UnknownTermOperator error"),
+        }
+    }
+
+    #[test]
+    fn negative_term_operators_are_also_term_operators() {
+        assert!(NEGATIVE_TERM_OPERATORS
+            .iter()
+            .all(|op| TERM_OPERATORS.contains(op)));
+    }
+
+    #[test]
+    fn params_are_strongly_typed_values() {
+        let age = 18;
+        let items = [DomainItem::Term("age", ">=", &age)];
+        let domain = Domain::parse(&items).unwrap();
+        assert_eq!(vec![Value::Int(18)], domain.compile().params());
+    }
+}
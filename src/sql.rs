@@ -1,4 +1,122 @@
-use std::any::Any;
+use std::collections::HashMap;
+
+/// An owned SQL parameter value.
+///
+/// This is what every bound parameter is converted to via [ToSql] before being
+/// stored on a [Sql] object, so consumers never have to downcast a type-erased
+/// value to know what they're holding.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// SQL `NULL`, produced by `None`.
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    /// Render this value as a SQL literal, for [Sql::to_unsafe_string].
+    ///
+    /// Strings are single-quoted with embedded quotes doubled; numbers and
+    /// booleans are rendered literally; [Value::Null] renders as `NULL`.
+    fn to_unsafe_sql(&self) -> String {
+        match self {
+            Value::Null => "NULL".to_owned(),
+            Value::Bool(value) => value.to_string(),
+            Value::Int(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::Text(value) => format!("'{}'", value.replace('\'', "''")),
+            Value::Blob(bytes) => {
+                let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                format!("x'{hex}'")
+            }
+        }
+    }
+}
+
+/// Converts a Rust value into an owned [Value] for binding to a [Sql] parameter.
+///
+/// Implemented for the common scalar types (`&str`, `String`, integers, floats,
+/// `bool`, byte slices) and for `Option<T>` (`None` becomes [Value::Null]). A
+/// blanket impl over `&T` means callers can keep passing references, e.g. `&42`.
+pub trait ToSql {
+    fn to_sql(&self) -> Value;
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ToSql for str {
+    fn to_sql(&self) -> Value {
+        Value::Text(self.to_owned())
+    }
+}
+
+impl ToSql for String {
+    fn to_sql(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToSql for bool {
+    fn to_sql(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToSql for [u8] {
+    fn to_sql(&self) -> Value {
+        Value::Blob(self.to_vec())
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn to_sql(&self) -> Value {
+        Value::Blob(self.clone())
+    }
+}
+
+macro_rules! impl_to_sql_int {
+    ($($t:ty),*) => {
+        $(impl ToSql for $t {
+            fn to_sql(&self) -> Value {
+                Value::Int(*self as i64)
+            }
+        })*
+    };
+}
+impl_to_sql_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_to_sql_float {
+    ($($t:ty),*) => {
+        $(impl ToSql for $t {
+            fn to_sql(&self) -> Value {
+                Value::Float(*self as f64)
+            }
+        })*
+    };
+}
+impl_to_sql_float!(f32, f64);
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql(&self) -> Value {
+        match self {
+            Some(value) => value.to_sql(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for &T {
+    fn to_sql(&self) -> Value {
+        (**self).to_sql()
+    }
+}
 
 /// An object that wraps SQL code with its parameters, like::
 ///```
@@ -12,22 +130,36 @@ use std::any::Any;
 /// The purpose of this object is to prevent SQL injection attacks and
 /// make SQL queries safer.
 #[derive(Clone)]
-pub struct Sql<'a> {
+pub struct Sql {
     query: String,
     fragment: String,
-    params: Vec<&'a dyn Any>,
+    params: Vec<Value>,
+    named: HashMap<String, Value>,
+}
+
+/// An error produced while resolving a [Sql] fragment at [Sql::finalize_for] time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlError {
+    /// A `:name` placeholder appeared in the fragment but no value was ever
+    /// bound to it with [Sql::push_bind_param].
+    UnboundParameter(String),
 }
 
-impl<'a> Sql<'a> {
+impl Sql {
     /// Create a new instance of [Sql].
     ///
     /// The [fragment] argument is a ``?``-formatted string containing the SQL query.
+    /// It is copied into the returned object, so it may be built on the fly (e.g. with
+    /// `format!`).
+    ///
+    /// Each parameter is converted to an owned [Value] via [ToSql] immediately, so the
+    /// returned object holds no borrows into `params`.
     ///
     /// # returns
     /// A new [Sql] object.
-    pub fn new(fragment: &'a str, params: Option<&mut [&'a dyn Any]>) -> Sql<'a> {
+    pub fn new(fragment: &str, params: Option<&mut [&dyn ToSql]>) -> Sql {
         let params = match params {
-            Some(args) => args.to_vec(),
+            Some(args) => args.iter().map(|arg| arg.to_sql()).collect(),
             None => Vec::new(),
         };
 
@@ -35,28 +167,46 @@ impl<'a> Sql<'a> {
             query: String::new(),
             fragment: fragment.to_owned(),
             params,
+            named: HashMap::new(),
         }
     }
 
     /// Add another [Sql] object to the end of this object.
-    /// 
+    ///
     /// The string fragment of the ``sql`` object is concatenated to this one
     /// and the parameters from the ``sql`` are also added to the end of the
     /// parameter list of this object.
-    /// 
+    ///
     /// # Returns
     /// The current instance of [Sql] with the added parts.
-    pub fn append(mut self, sql: Sql<'a>) -> Sql<'a> {
-        let mut params = sql.params().to_vec();
+    pub fn append(mut self, sql: Sql) -> Sql {
+        let mut params = sql.params();
         self.fragment.push(' ');
         self.fragment += &sql.fragment;
         self.params.append(&mut params);
+        self.named.extend(sql.named);
+
+        self
+    }
+
+    /// Bind a named parameter, referenced in the fragment as `:name`.
+    ///
+    /// Unlike the positional `?` parameters passed to [Sql::new], a named
+    /// parameter can be referenced more than once in the fragment; at
+    /// [Sql::finalize_for] time every `:name` occurrence resolves back to the
+    /// single value bound here. Binding the same name again replaces the
+    /// previous value.
+    ///
+    /// # Returns
+    /// The current instance of [Sql] with the parameter bound.
+    pub fn push_bind_param<T: ToSql>(mut self, name: &str, value: T) -> Sql {
+        self.named.insert(name.to_owned(), value.to_sql());
 
         self
     }
 
     /// The string fragment.
-    /// 
+    ///
     /// # Retruns
     /// The string fragment stored in the [Sql] object.
     pub fn query(&self) -> String {
@@ -64,47 +214,216 @@ impl<'a> Sql<'a> {
     }
 
     /// The list of parameters.
-    /// 
+    ///
     /// # Returns
     /// A vector containing the parameters to be inserted into the query.
-    pub fn params(&self) -> Vec<&'a dyn Any> {
+    pub fn params(&self) -> Vec<Value> {
         self.params.clone()
     }
 
     /// The final formatted query
-    /// 
+    ///
     /// # Returns
     /// A string containing the formatted query to be passed into a query engine.
     pub fn formatted(&self) -> String {
         self.query.clone()
     }
 
-    /// Finalize the query.
-    /// 
+    /// Render the query with its parameters inlined in place of each ``?``.
+    ///
+    /// **This output is injection-unsafe and must never be executed** — it exists
+    /// purely for logging and debugging, so a developer can see the concrete
+    /// predicate a compiled domain expands to. Strings are single-quoted with
+    /// embedded quotes doubled, numbers and booleans are rendered literally, and
+    /// a bound `None` renders as `NULL`.
+    pub fn to_unsafe_string(&self) -> String {
+        let mut params = self.params.iter();
+        let mut out = String::new();
+        for ch in self.fragment.chars() {
+            if ch == '?' {
+                match params.next() {
+                    Some(value) => out += &value.to_unsafe_sql(),
+                    None => out.push('?'),
+                }
+            } else {
+                out.push(ch);
+            }
+        }
+
+        out
+    }
+
+    /// Finalize the query for PostgreSQL.
+    ///
     /// The query parameters in the query string will be numbered. Each ``?`` in
     /// the query string will be changed into a ``$`` followed by a number denoting
     /// the position of the parameter to be substituted (beginning with 1, not 0).
-    pub fn finalize(mut self) -> Sql<'a> {
-        let mut idx = 1;
+    ///
+    /// This is a shorthand for `finalize_for(Dialect::Postgres)`; use
+    /// [Sql::finalize_for] to target another engine.
+    pub fn finalize(self) -> Result<Sql, SqlError> {
+        self.finalize_for(Dialect::Postgres)
+    }
+
+    /// Finalize the query for the given [Dialect].
+    ///
+    /// Every ``?`` and ``:name`` placeholder in the fragment is rewritten into the
+    /// placeholder style the dialect expects, and [Sql::params] is rebuilt to match
+    /// it value-for-value: ``$1``, ``$2``, ... for [Dialect::Postgres], ``?1``,
+    /// ``?2``, ... for [Dialect::SqliteNumbered], and a bare ``?`` for
+    /// [Dialect::Sqlite], [Dialect::MySql], and [Dialect::AnsiQuestionMark].
+    ///
+    /// A `:name` placeholder resolves to the value bound with
+    /// [Sql::push_bind_param]. For the numbered dialects, every occurrence of the
+    /// same name reuses a single parameter slot; for the bare-`?` dialects, which
+    /// have no way to reference a slot twice, the value is repeated once per
+    /// occurrence instead. Referencing a name that was never bound is an error.
+    pub fn finalize_for(mut self, dialect: Dialect) -> Result<Sql, SqlError> {
+        let mut idx = 0;
+        let mut slots: HashMap<String, usize> = HashMap::new();
+        let mut resolved = Vec::new();
+        let mut positional = self.params.into_iter();
+        let chars: Vec<char> = self.fragment.chars().collect();
         let mut query = String::new();
-        for ch in self.fragment.chars() {
-            if ch == '?' {
-                query += &format!("${idx}");
-                idx += 1;
-            } else {
-                query.push(ch);
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '?' => {
+                    idx += 1;
+                    resolved.push(positional.next().unwrap_or(Value::Null));
+                    Self::push_placeholder(&mut query, dialect, idx);
+                    i += 1;
+                }
+                ':' if i.checked_sub(1).and_then(|j| chars.get(j)) != Some(&':')
+                    && chars.get(i + 1).is_some_and(|c| c.is_alphabetic() || *c == '_') =>
+                {
+                    let start = i + 1;
+                    let mut end = start;
+                    while chars.get(end).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+                        end += 1;
+                    }
+                    let name: String = chars[start..end].iter().collect();
+                    let value = self
+                        .named
+                        .get(&name)
+                        .cloned()
+                        .ok_or_else(|| SqlError::UnboundParameter(name.clone()))?;
+
+                    match dialect {
+                        Dialect::Postgres | Dialect::SqliteNumbered => {
+                            let slot = *slots.entry(name).or_insert_with(|| {
+                                idx += 1;
+                                resolved.push(value);
+                                idx
+                            });
+                            Self::push_placeholder(&mut query, dialect, slot);
+                        }
+                        Dialect::Sqlite | Dialect::MySql | Dialect::AnsiQuestionMark => {
+                            idx += 1;
+                            resolved.push(value);
+                            query.push('?');
+                        }
+                    }
+                    i = end;
+                }
+                ch => {
+                    query.push(ch);
+                    i += 1;
+                }
             }
         }
+
         self.query = query;
+        self.params = resolved;
 
-        self
+        Ok(self)
     }
 
-    pub fn identifier(arg: & str) -> String {
-        format!(r#""{arg}""#)
+    /// Write the placeholder for parameter number `idx` (1-based) for `dialect`.
+    fn push_placeholder(query: &mut String, dialect: Dialect, idx: usize) {
+        match dialect {
+            Dialect::Postgres => *query += &format!("${idx}"),
+            Dialect::SqliteNumbered => *query += &format!("?{idx}"),
+            Dialect::Sqlite | Dialect::MySql | Dialect::AnsiQuestionMark => query.push('?'),
+        }
+    }
+
+    /// Quote `arg` as an identifier using ANSI double-quoting.
+    ///
+    /// This is a shorthand for `identifier_for(arg, Dialect::AnsiQuestionMark)`;
+    /// use [Sql::identifier_for] to target a dialect with different quoting (e.g.
+    /// MySQL's backticks).
+    pub fn identifier(arg: &str) -> String {
+        Self::identifier_for(arg, Dialect::AnsiQuestionMark)
+    }
+
+    /// Quote `arg` as an identifier for the given [Dialect].
+    ///
+    /// MySQL quotes identifiers with backticks; every other dialect here uses
+    /// ANSI double-quotes.
+    pub fn identifier_for(arg: &str, dialect: Dialect) -> String {
+        match dialect {
+            Dialect::MySql => format!("`{arg}`"),
+            Dialect::Postgres | Dialect::Sqlite | Dialect::SqliteNumbered | Dialect::AnsiQuestionMark => {
+                format!(r#""{arg}""#)
+            }
+        }
     }
 }
 
+/// An abstract target for SQL fragment accumulation, identifier quoting, and
+/// positional parameter binding.
+///
+/// [Sql] is the default, in-memory implementation, but compiling against this
+/// trait instead of [Sql] directly lets a consumer target something else —
+/// e.g. a builder that streams straight into a prepared-statement binder, or
+/// one that only counts parameters without materializing any SQL text.
+pub trait QueryBuilder {
+    /// Append a raw SQL fragment, verbatim.
+    fn push_sql(&mut self, sql: &str);
+    /// Append a quoted identifier.
+    fn push_identifier(&mut self, name: &str);
+    /// Append a `?` placeholder and bind `value` to it, positionally.
+    fn push_param(&mut self, value: Value);
+}
+
+impl QueryBuilder for Sql {
+    fn push_sql(&mut self, sql: &str) {
+        self.fragment.push_str(sql);
+    }
+
+    fn push_identifier(&mut self, name: &str) {
+        self.fragment.push_str(&Self::identifier(name));
+    }
+
+    fn push_param(&mut self, value: Value) {
+        self.fragment.push('?');
+        self.params.push(value);
+    }
+}
+
+/// A SQL dialect, used to pick placeholder syntax and identifier quoting.
+///
+/// Domain compilation produces dialect-neutral ``?`` placeholders and ANSI
+/// double-quoted identifiers. Passing a [Dialect] to [Sql::finalize_for]
+/// rewrites the placeholders for a specific engine; identifiers already in the
+/// fragment keep their double quotes. [Sql::identifier_for] quotes a single
+/// identifier for a specific engine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// PostgreSQL: `$1`, `$2`, ... numbered placeholders.
+    Postgres,
+    /// SQLite: bare `?` placeholders.
+    Sqlite,
+    /// SQLite: `?1`, `?2`, ... numbered placeholders.
+    SqliteNumbered,
+    /// MySQL: bare `?` placeholders, backtick-quoted identifiers.
+    MySql,
+    /// Any ANSI-SQL engine that expects bare `?` placeholders.
+    AnsiQuestionMark,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,8 +470,8 @@ mod tests {
             "Sql object statement() returns the fragment part"
         );
         assert_eq!(
-            *sql.params().pop().unwrap().downcast_ref::<&str>().unwrap(),
-            "foo",
+            sql.params().pop().unwrap(),
+            Value::Text("foo".to_owned()),
             "The first parameter returned contains 'foo'"
         );
     }
@@ -172,19 +491,161 @@ mod tests {
         );
         let params = sql.params();
         assert_eq!(
-            *params[0].downcast_ref::<String>().unwrap(),
-            r#""foo""#,
-            "The first parameter is &str"
-        );
-        assert_eq!(
-            *params[1].downcast_ref::<i32>().unwrap(),
-            1,
-            "The second parameter is an int"
+            params[0],
+            Value::Text(r#""foo""#.to_owned()),
+            "The first parameter is the quoted identifier"
         );
+        assert_eq!(params[1], Value::Int(1), "The second parameter is an int");
         assert_eq!(
             "UPDATE TABLE \"foo\" SET name=$1, one=$2",
-            sql.finalize().formatted(),
+            sql.finalize().unwrap().formatted(),
             "Sql object statement() returns the fragment part"
         );
     }
+
+    #[test]
+    fn none_becomes_null() {
+        let value: Option<i32> = None;
+        let sql = Sql::new("WHERE col = ?", Some(&mut [&value]));
+
+        assert_eq!(
+            Value::Null,
+            sql.params().pop().unwrap(),
+            "None is converted to Value::Null"
+        );
+    }
+
+    #[test]
+    fn finalize_for_postgres_numbers_with_dollar_sign() {
+        let sql = Sql::new("a=? AND b=?", None)
+            .finalize_for(Dialect::Postgres)
+            .unwrap();
+        assert_eq!("a=$1 AND b=$2", sql.formatted());
+    }
+
+    #[test]
+    fn finalize_for_postgres_leaves_cast_operator_alone() {
+        let sql = Sql::new("col::text = ?", Some(&mut [&"foo"]))
+            .finalize_for(Dialect::Postgres)
+            .unwrap();
+        assert_eq!("col::text = $1", sql.formatted());
+    }
+
+    #[test]
+    fn finalize_for_sqlite_leaves_bare_question_marks() {
+        let sql = Sql::new("a=? AND b=?", None)
+            .finalize_for(Dialect::Sqlite)
+            .unwrap();
+        assert_eq!("a=? AND b=?", sql.formatted());
+    }
+
+    #[test]
+    fn finalize_for_sqlite_numbered_numbers_with_question_mark() {
+        let sql = Sql::new("a=? AND b=?", None)
+            .finalize_for(Dialect::SqliteNumbered)
+            .unwrap();
+        assert_eq!("a=?1 AND b=?2", sql.formatted());
+    }
+
+    #[test]
+    fn finalize_for_mysql_leaves_bare_question_marks() {
+        let sql = Sql::new("a=? AND b=?", None)
+            .finalize_for(Dialect::MySql)
+            .unwrap();
+        assert_eq!("a=? AND b=?", sql.formatted());
+    }
+
+    #[test]
+    fn identifier_for_mysql_uses_backticks() {
+        assert_eq!("`foo`", Sql::identifier_for("foo", Dialect::MySql));
+    }
+
+    #[test]
+    fn identifier_for_postgres_uses_double_quotes() {
+        assert_eq!(r#""foo""#, Sql::identifier_for("foo", Dialect::Postgres));
+    }
+
+    #[test]
+    fn to_unsafe_string_inlines_parameters() {
+        let name = "O'Brien";
+        let age = 42;
+        let active: Option<bool> = None;
+        let sql = Sql::new(
+            "name=? AND age=? AND active=?",
+            Some(&mut [&name, &age, &active]),
+        );
+
+        assert_eq!(
+            "name='O''Brien' AND age=42 AND active=NULL",
+            sql.to_unsafe_string()
+        );
+    }
+
+    #[test]
+    fn to_unsafe_string_leaves_unbound_placeholders_alone() {
+        let sql = Sql::new("a=?", None);
+        assert_eq!("a=?", sql.to_unsafe_string());
+    }
+
+    #[test]
+    fn named_param_reuses_one_slot_for_a_numbered_dialect() {
+        let sql = Sql::new("start >= :start_date AND end <= :start_date", None)
+            .push_bind_param("start_date", "2024-01-01")
+            .finalize_for(Dialect::Postgres)
+            .unwrap();
+
+        assert_eq!("start >= $1 AND end <= $1", sql.formatted());
+        assert_eq!(vec![Value::Text("2024-01-01".to_owned())], sql.params());
+    }
+
+    #[test]
+    fn named_param_is_repeated_for_a_bare_question_mark_dialect() {
+        let sql = Sql::new("start >= :start_date AND end <= :start_date", None)
+            .push_bind_param("start_date", "2024-01-01")
+            .finalize_for(Dialect::MySql)
+            .unwrap();
+
+        assert_eq!("start >= ? AND end <= ?", sql.formatted());
+        assert_eq!(
+            vec![
+                Value::Text("2024-01-01".to_owned()),
+                Value::Text("2024-01-01".to_owned())
+            ],
+            sql.params()
+        );
+    }
+
+    #[test]
+    fn unbound_named_param_is_an_error() {
+        let sql = Sql::new("a = :missing", None);
+        match sql.finalize_for(Dialect::Postgres) {
+            Err(e) => assert_eq!(SqlError::UnboundParameter("missing".to_owned()), e),
+            Ok(_) => panic!("expected an UnboundParameter error"),
+        }
+    }
+
+    #[test]
+    fn named_params_mix_with_positional_params() {
+        let sql = Sql::new("a=? AND b=:name", Some(&mut [&1]))
+            .push_bind_param("name", "foo")
+            .finalize_for(Dialect::Postgres)
+            .unwrap();
+
+        assert_eq!("a=$1 AND b=$2", sql.formatted());
+        assert_eq!(
+            vec![Value::Int(1), Value::Text("foo".to_owned())],
+            sql.params()
+        );
+    }
+
+    #[test]
+    fn sql_implements_query_builder() {
+        let mut sql = Sql::new("", None);
+        sql.push_identifier("age");
+        sql.push_sql(" >= ");
+        sql.push_param(Value::Int(18));
+
+        assert_eq!(r#""age" >= ?"#, sql.query());
+        assert_eq!(vec![Value::Int(18)], sql.params());
+    }
 }